@@ -0,0 +1,83 @@
+//! Alias and `$VAR` expansion, run on the tokenized input between `Shlex` splitting and
+//! command dispatch.
+
+use shlex::Shlex;
+use std::{collections::HashSet, env};
+
+use crate::state::ShellState;
+
+/// Expands `alias` definitions: if the first token names an alias, it's replaced by the
+/// alias's re-tokenized command text, repeating until the first token is no longer an alias.
+/// Guards against a chain of aliases looping back on itself.
+pub fn expand_aliases(mut tokens: Vec<String>, state: &ShellState) -> Vec<String> {
+    let mut seen = HashSet::new();
+
+    while let Some(first) = tokens.first().cloned() {
+        if !seen.insert(first.clone()) {
+            break;
+        }
+        let Some(expansion) = state.aliases.get(&first) else {
+            break;
+        };
+
+        let mut expanded: Vec<String> = Shlex::new(expansion).collect();
+        expanded.extend(tokens.drain(1..));
+        tokens = expanded;
+    }
+
+    tokens
+}
+
+/// Expands `$NAME`, `${NAME}`, and `$?` within every token, the way POSIX shells interpolate
+/// variables before a command is dispatched.
+pub fn expand_variables(tokens: Vec<String>, state: &ShellState) -> Vec<String> {
+    tokens.iter().map(|t| expand_token(t, state)).collect()
+}
+
+fn expand_token(token: &str, state: &ShellState) -> String {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.peek().copied() {
+            Some('?') => {
+                chars.next();
+                out.push_str(&state.last_status.to_string());
+            }
+            Some('{') => {
+                chars.next();
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                out.push_str(&lookup(&name, state));
+            }
+            Some(c2) if c2.is_alphabetic() || c2 == '_' => {
+                let mut name = String::new();
+                while let Some(&c2) = chars.peek() {
+                    if c2.is_alphanumeric() || c2 == '_' {
+                        name.push(c2);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                out.push_str(&lookup(&name, state));
+            }
+            _ => out.push('$'),
+        }
+    }
+
+    out
+}
+
+fn lookup(name: &str, state: &ShellState) -> String {
+    state
+        .env
+        .get(name)
+        .cloned()
+        .or_else(|| env::var(name).ok())
+        .unwrap_or_default()
+}
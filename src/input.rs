@@ -0,0 +1,196 @@
+//! Interactive line editing: cursor movement, backspace, and TAB completion against builtins,
+//! `PATH` executables, and file paths, the way an interactive POSIX shell behaves.
+
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process,
+};
+
+use termios::{tcsetattr, Termios, ECHO, ICANON, ISIG, TCSANOW};
+
+use crate::BUILTINS;
+
+const STDIN_FD: i32 = 0;
+
+/// Reads one line of input from the terminal. When stdin is a real terminal this enables raw
+/// mode for cursor editing (left/right arrows, backspace) and TAB completion; otherwise (e.g.
+/// stdin is piped) it falls back to a plain line read with no editing.
+pub fn read_line(prompt: &str, env_path: &str) -> io::Result<String> {
+    match Termios::from_fd(STDIN_FD) {
+        Ok(original) => {
+            let mut raw = original;
+            raw.c_lflag &= !(ICANON | ECHO | ISIG);
+            tcsetattr(STDIN_FD, TCSANOW, &raw)?;
+
+            let result = read_line_raw(prompt, env_path);
+
+            tcsetattr(STDIN_FD, TCSANOW, &original)?;
+            println!();
+            result
+        }
+        Err(_) => {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+            read_line_plain()
+        }
+    }
+}
+
+fn read_line_plain() -> io::Result<String> {
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line)
+}
+
+fn read_line_raw(prompt: &str, env_path: &str) -> io::Result<String> {
+    let mut line = String::new();
+    let mut cursor = 0usize;
+    let stdin = io::stdin();
+    let mut bytes = stdin.lock().bytes();
+
+    redraw(prompt, &line, cursor);
+
+    while let Some(byte) = bytes.next() {
+        match byte? {
+            b'\r' | b'\n' => break,
+            0x04 if line.is_empty() => process::exit(0), // Ctrl-D on an empty line
+            0x03 => {
+                // Ctrl-C: abandon the current line, same as a fresh prompt
+                line.clear();
+                break;
+            }
+            0x7f | 0x08 if cursor > 0 => {
+                line.remove(cursor - 1);
+                cursor -= 1;
+            }
+            b'\t' => complete(&mut line, &mut cursor, env_path),
+            0x1b => {
+                if let (Some(Ok(b'[')), Some(Ok(c))) = (bytes.next(), bytes.next()) {
+                    match c {
+                        b'C' if cursor < line.len() => cursor += 1, // right arrow
+                        b'D' if cursor > 0 => cursor -= 1,          // left arrow
+                        _ => {}
+                    }
+                }
+            }
+            b if b.is_ascii() && !b.is_ascii_control() => {
+                line.insert(cursor, b as char);
+                cursor += 1;
+            }
+            _ => {}
+        }
+        redraw(prompt, &line, cursor);
+    }
+
+    Ok(line)
+}
+
+fn redraw(prompt: &str, line: &str, cursor: usize) {
+    let mut out = io::stdout();
+    write!(out, "\r\x1b[K{}{}", prompt, line).unwrap();
+    let trailing = line.len() - cursor;
+    if trailing > 0 {
+        write!(out, "\x1b[{}D", trailing).unwrap();
+    }
+    out.flush().unwrap();
+}
+
+/// Completes the word under the cursor: the first word completes against builtins and `PATH`
+/// executables, any later word completes against file paths. A single match is inserted
+/// directly; several matches print the candidate list and complete the longest common prefix,
+/// mirroring the moros shell's completer.
+fn complete(line: &mut String, cursor: &mut usize, env_path: &str) {
+    let word_start = line[..*cursor].rfind(' ').map_or(0, |i| i + 1);
+    let is_first_word = line[..word_start].trim().is_empty();
+    let prefix = line[word_start..*cursor].to_string();
+
+    let candidates = if is_first_word {
+        complete_command(&prefix, env_path)
+    } else {
+        complete_path(&prefix)
+    };
+
+    match candidates.len() {
+        0 => {}
+        1 => replace_word(line, cursor, word_start, &candidates[0]),
+        _ => {
+            print!("\r\n{}\r\n", candidates.join("  "));
+            let common = longest_common_prefix(&candidates);
+            if common.len() > prefix.len() {
+                replace_word(line, cursor, word_start, &common);
+            }
+        }
+    }
+}
+
+fn replace_word(line: &mut String, cursor: &mut usize, word_start: usize, replacement: &str) {
+    line.replace_range(word_start..*cursor, replacement);
+    *cursor = word_start + replacement.len();
+}
+
+fn complete_command(prefix: &str, env_path: &str) -> Vec<String> {
+    let mut candidates: Vec<String> = BUILTINS
+        .iter()
+        .map(|b| b.to_string())
+        .chain(path_executables(env_path))
+        .filter(|c| c.starts_with(prefix))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+fn path_executables(env_path: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for dir in env::split_paths(env_path) {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.path().is_file() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+fn complete_path(prefix: &str) -> Vec<String> {
+    let (dir, file_prefix) = match prefix.rfind('/') {
+        Some(i) => (&prefix[..=i], &prefix[i + 1..]),
+        None => ("", prefix),
+    };
+    let search_dir = if dir.is_empty() { "." } else { dir };
+
+    let mut candidates = Vec::new();
+    if let Ok(entries) = fs::read_dir(search_dir) {
+        for entry in entries.flatten() {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !name.starts_with(file_prefix) {
+                continue;
+            }
+            let mut candidate = format!("{}{}", dir, name);
+            if entry.path().is_dir() {
+                candidate.push('/');
+            }
+            candidates.push(candidate);
+        }
+    }
+    candidates.sort();
+    candidates
+}
+
+fn longest_common_prefix(items: &[String]) -> String {
+    let mut prefix = items[0].clone();
+    for item in &items[1..] {
+        while !item.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+    prefix
+}
@@ -1,81 +1,313 @@
+mod expand;
+mod input;
+mod state;
+
 use shlex::Shlex;
 #[allow(unused_imports)]
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::{
+    cell::RefCell,
     env,
     fs::{File, OpenOptions},
-    io::Error,
+    os::unix::process::ExitStatusExt,
     path::{Path, PathBuf},
-    process::{self, Command},
+    process::{self, Child, Command, ExitStatus, Stdio},
+    rc::Rc,
 };
 
-const BUILTINS: [&str; 4] = ["echo", "type", "pwd", "exit"];
+use state::ShellState;
+
+pub(crate) const BUILTINS: [&str; 7] = ["echo", "type", "pwd", "exit", "cd", "export", "alias"];
+const REDIR_INPUT_PATTERN: &str = "<";
 const REDIR_WRITE_PATTERNS: [&str; 3] = [">", "1>", "2>"];
 const REDIR_APPEND_PATTERNS: [&str; 3] = [">>", "1>>", "2>>"];
+const REDIR_DUP_PATTERNS: [&str; 2] = ["2>&1", "1>&2"];
 
 fn main() {
     let path = std::env::var("PATH").unwrap();
+    let mut state = ShellState::default();
 
     loop {
-        let mut stdout_buffer: Box<dyn Write> = Box::new(io::stdout());
-        let mut stderr_buffer: Box<dyn Write> = Box::new(io::stderr());
-
-        write_to_buffer("$ ", &mut stdout_buffer);
-
-        // Wait for user input
-        let stdin = io::stdin();
-        let mut input = String::new();
-        stdin.read_line(&mut input).unwrap();
-        let trimmed_input = input.trim();
+        let trimmed_input = match input::read_line("$ ", &path) {
+            Ok(line) => line.trim().to_string(),
+            Err(err) => {
+                eprintln!("Failed to read input: {}", err);
+                continue;
+            }
+        };
 
         if trimmed_input.is_empty() {
             // noop
             continue;
         }
 
-        let posix_friendly_input: Vec<String> = Shlex::new(trimmed_input).collect();
-        let mut cmds: Vec<&str> = posix_friendly_input.iter().map(|v| v.as_str()).collect();
+        let tokens: Vec<String> = Shlex::new(&trimmed_input).collect();
+        let tokens = expand::expand_aliases(tokens, &state);
+        let tokens = expand::expand_variables(tokens, &state);
+        let cmds: Vec<&str> = tokens.iter().map(|v| v.as_str()).collect();
+
+        // Each `;`/`&&`/`||`-separated command gets its own redirection context: a file one
+        // command redirects into must not leak into the next command on the same line.
+        let mut pending_connector = None;
+        for (group, connector) in split_sequence(&cmds) {
+            let should_run = match pending_connector {
+                None | Some(Connector::Seq) => true,
+                Some(Connector::And) => state.last_status == 0,
+                Some(Connector::Or) => state.last_status != 0,
+            };
+            pending_connector = connector;
+
+            if !should_run || group.is_empty() {
+                continue;
+            }
+
+            let mut stdout_buffer: Box<dyn Write> = Box::new(io::stdout());
+            let mut stderr_buffer: Box<dyn Write> = Box::new(io::stderr());
+
+            match group[..] {
+                ["exit"] => exit_cmd(None, &state),
+                ["exit", code] => exit_cmd(Some(code), &state),
+                _ => {
+                    if let Err(err) = run_pipeline(
+                        &path,
+                        group,
+                        &mut state,
+                        &mut stdout_buffer,
+                        &mut stderr_buffer,
+                    ) {
+                        println!("{}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How a command group is tied to the one before it: `;` always runs the next group, `&&`
+/// only runs it after a zero exit status, `||` only after a non-zero one.
+enum Connector {
+    Seq,
+    And,
+    Or,
+}
+
+/// Splits `cmds` on `;`, `&&`, and `||` into command groups paired with the connector that
+/// follows each one (`None` for the last group), left to right and at lower precedence than
+/// the `|` pipe split inside `run_pipeline`.
+fn split_sequence<'a>(cmds: &[&'a str]) -> Vec<(Vec<&'a str>, Option<Connector>)> {
+    let mut groups = Vec::new();
+    let mut current = Vec::new();
+
+    for &token in cmds {
+        match token {
+            ";" => groups.push((std::mem::take(&mut current), Some(Connector::Seq))),
+            "&&" => groups.push((std::mem::take(&mut current), Some(Connector::And))),
+            "||" => groups.push((std::mem::take(&mut current), Some(Connector::Or))),
+            _ => current.push(token),
+        }
+    }
+    groups.push((current, None));
+
+    groups
+}
+
+/// A `Write` sink backed by a ref-counted buffer, so the bytes a builtin writes to it can be
+/// read back out once the call returns (used to feed a builtin's output into the next stage
+/// of a pipeline).
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Splits `cmds` on `|` into pipeline segments and runs each one in turn, wiring the stdout of
+/// every non-final segment into the stdin of the next (via a real OS pipe for external commands,
+/// or a captured buffer for builtins, which don't have a stdout of their own to pipe). Only the
+/// first segment's redirection can affect stdin (it's the only one that would otherwise read
+/// from the inherited stdin), and only the final segment's redirection affects stdout/stderr,
+/// since that's the only segment whose output reaches `stdout_buffer`/`stderr_buffer` rather
+/// than the next stage.
+fn run_pipeline(
+    env_path: &str,
+    cmds: Vec<&str>,
+    state: &mut ShellState,
+    stdout_buffer: &mut Box<dyn Write>,
+    stderr_buffer: &mut Box<dyn Write>,
+) -> Result<(), &'static str> {
+    let mut segments: Vec<Vec<&str>> = vec![Vec::new()];
+    for &token in &cmds {
+        if token == "|" {
+            segments.push(Vec::new());
+        } else {
+            segments.last_mut().unwrap().push(token);
+        }
+    }
+
+    if segments.iter().any(Vec::is_empty) {
+        return Err("syntax error near unexpected token `|'");
+    }
+
+    let last_idx = segments.len() - 1;
+
+    let mut next_stdin = Stdio::inherit();
+    let first_redirections = parse_redirection(&mut segments[0])?;
+    if last_idx == 0 {
+        apply_redirections(first_redirections, &mut next_stdin, stdout_buffer, stderr_buffer)?;
+    } else {
+        let mut unused_stdout: Box<dyn Write> = Box::new(io::sink());
+        let mut unused_stderr: Box<dyn Write> = Box::new(io::sink());
+        apply_redirections(
+            first_redirections,
+            &mut next_stdin,
+            &mut unused_stdout,
+            &mut unused_stderr,
+        )?;
+
+        let last_redirections = parse_redirection(&mut segments[last_idx])?;
+        apply_redirections(
+            last_redirections,
+            &mut Stdio::null(),
+            stdout_buffer,
+            stderr_buffer,
+        )?;
+    }
+
+    if segments[0].is_empty() || segments[last_idx].is_empty() {
+        return Err("syntax error: missing command");
+    }
 
-        if let Err(err) = parse_redirection(&mut cmds, &mut stdout_buffer, &mut stderr_buffer) {
-            println!("Failed to parse redirection: {}", err);
+    let mut children: Vec<Child> = Vec::new();
+    let mut carried_bytes: Option<Vec<u8>> = None;
+
+    for (idx, segment) in segments.into_iter().enumerate() {
+        let is_last = idx == last_idx;
+        let cmd = segment[0];
+        let stdin = std::mem::replace(&mut next_stdin, Stdio::inherit());
+        let stdin_bytes = carried_bytes.take();
+
+        if BUILTINS.contains(&cmd) {
+            if is_last {
+                run_builtin(env_path, &segment, state, stdout_buffer, stderr_buffer);
+            } else {
+                let shared = SharedBuffer::default();
+                let mut captured: Box<dyn Write> = Box::new(shared.clone());
+                run_builtin(env_path, &segment, state, &mut captured, stderr_buffer);
+                carried_bytes = Some(shared.take());
+            }
             continue;
         }
 
-        match cmds[..] {
-            ["exit"] => process::exit(0),
-            ["exit", code] => process::exit(code.parse::<i32>().unwrap()),
-            ["pwd", ..] => pwd_cmd(cmds, &mut stdout_buffer),
-            ["type", ..] => type_cmd(cmds[1..].to_vec(), &path, &mut stdout_buffer),
-            ["echo", ..] => echo_cmd(cmds[1..].to_vec(), &mut stdout_buffer),
-            ["cd", ..] => cd_cmd(cmds[1..].to_vec(), &mut stdout_buffer),
-            _ => try_external_cmd(&path, cmds, &mut stdout_buffer, &mut stderr_buffer),
+        match try_external_cmd(
+            env_path,
+            &segment,
+            stdin,
+            stdin_bytes,
+            is_last,
+            state,
+            stdout_buffer,
+            stderr_buffer,
+        ) {
+            Some(mut child) => {
+                if !is_last {
+                    next_stdin = Stdio::from(child.stdout.take().unwrap());
+                }
+                children.push(child);
+            }
+            // Command not found: no further stage gets spawned, so this leg of the
+            // pipeline's read end is simply dropped rather than left open.
+            None => break,
         }
     }
+
+    for mut child in children {
+        child.wait().expect("failed to wait on child");
+    }
+
+    Ok(())
+}
+
+/// Runs one of `BUILTINS` against an already-split command segment, the same dispatch `main`
+/// used to do directly before pipelines made a segment's sink potentially not be the real
+/// stdout/stderr.
+fn run_builtin(
+    env_path: &str,
+    cmds: &[&str],
+    state: &mut ShellState,
+    stdout_buffer: &mut Box<dyn Write>,
+    _stderr_buffer: &mut Box<dyn Write>,
+) {
+    match cmds {
+        ["exit"] => exit_cmd(None, state),
+        ["exit", code] => exit_cmd(Some(code), state),
+        ["pwd", ..] => pwd_cmd(cmds.to_vec(), state, stdout_buffer),
+        ["type", rest @ ..] => type_cmd(rest.to_vec(), env_path, state, stdout_buffer),
+        ["echo", rest @ ..] => echo_cmd(rest.to_vec(), state, stdout_buffer),
+        ["cd", rest @ ..] => cd_cmd(rest.to_vec(), state, stdout_buffer),
+        ["export", rest @ ..] => export_cmd(rest, state, stdout_buffer),
+        ["alias", rest @ ..] => alias_cmd(rest, state, stdout_buffer),
+        _ => unreachable!("run_builtin called with non-builtin command"),
+    }
+}
+
+/// Exits the shell. With no argument, exits with the status of the last command run (`$?`);
+/// with a non-numeric argument, reports the error and keeps the REPL alive instead of
+/// panicking, the way a real shell's `exit` handles a bad argument.
+fn exit_cmd(code_arg: Option<&str>, state: &ShellState) {
+    let code = match code_arg {
+        None => state.last_status,
+        Some(arg) => match arg.parse::<i32>() {
+            Ok(code) => code,
+            Err(_) => {
+                println!("exit: {}: numeric argument required", arg);
+                return;
+            }
+        },
+    };
+    process::exit(code);
 }
 
-fn echo_cmd(echo_strs: Vec<&str>, stdout_buffer: &mut Box<dyn Write>) {
-    writeln_to_buffer(&format!("{}", echo_strs.join(" ")), stdout_buffer);
+fn echo_cmd(echo_strs: Vec<&str>, state: &mut ShellState, stdout_buffer: &mut Box<dyn Write>) {
+    writeln_to_buffer(&echo_strs.join(" "), stdout_buffer);
+    state.last_status = 0;
 }
 
-fn pwd_cmd(cmds: Vec<&str>, stdout_buffer: &mut Box<dyn Write>) {
+fn pwd_cmd(cmds: Vec<&str>, state: &mut ShellState, stdout_buffer: &mut Box<dyn Write>) {
     if cmds.len() > 1 {
         writeln_to_buffer("too many arguments", stdout_buffer);
+        state.last_status = 1;
         return;
     }
 
     let current_dir = env::current_dir().unwrap();
     writeln_to_buffer(&format!("{}", current_dir.display()), stdout_buffer);
+    state.last_status = 0;
 }
 
-fn cd_cmd(cmds: Vec<&str>, stdout_buffer: &mut Box<dyn Write>) {
+fn cd_cmd(cmds: Vec<&str>, state: &mut ShellState, stdout_buffer: &mut Box<dyn Write>) {
     if cmds.len() > 1 {
         writeln_to_buffer("too many arguments", stdout_buffer);
+        state.last_status = 1;
         return;
     }
 
     if cmds.len() == 0 || cmds[0].starts_with("~") {
         // cd <blank> changes wd to home
         env::set_current_dir(env::var("HOME").unwrap()).unwrap();
+        state.last_status = 0;
         return;
     }
 
@@ -93,10 +325,18 @@ fn cd_cmd(cmds: Vec<&str>, stdout_buffer: &mut Box<dyn Write>) {
             &format!("cd: {}: No such file or directory", path.display()),
             stdout_buffer,
         );
+        state.last_status = 1;
+    } else {
+        state.last_status = 0;
     }
 }
 
-fn type_cmd(type_strs: Vec<&str>, env_path: &str, stdout_buffer: &mut Box<dyn Write>) {
+fn type_cmd(
+    type_strs: Vec<&str>,
+    env_path: &str,
+    state: &mut ShellState,
+    stdout_buffer: &mut Box<dyn Write>,
+) {
     if type_strs.len() != 1 {
         writeln_to_buffer(
             &format!(
@@ -105,20 +345,81 @@ fn type_cmd(type_strs: Vec<&str>, env_path: &str, stdout_buffer: &mut Box<dyn Wr
             ),
             stdout_buffer,
         );
+        state.last_status = 1;
         return;
     }
     let cmd = &type_strs[0];
 
-    if BUILTINS.contains(cmd) {
+    if let Some(expansion) = state.aliases.get(*cmd) {
+        writeln_to_buffer(&format!("{} is aliased to `{}`", cmd, expansion), stdout_buffer);
+        state.last_status = 0;
+    } else if BUILTINS.contains(cmd) {
         writeln_to_buffer(&format!("{} is a shell builtin", cmd), stdout_buffer);
+        state.last_status = 0;
     } else if let Some(external_cmd) = find_external_cmd(env_path, cmd) {
         writeln_to_buffer(
             &format!("{} is {}", cmd, external_cmd.display()),
             stdout_buffer,
         );
+        state.last_status = 0;
     } else {
         writeln_to_buffer(&format!("{}: not found", cmd), stdout_buffer);
+        state.last_status = 1;
+    }
+}
+
+/// `export NAME=value` adds a shell variable visible to `$NAME` expansion and, via
+/// `env::set_var`, to every child process spawned from here on.
+fn export_cmd(args: &[&str], state: &mut ShellState, stdout_buffer: &mut Box<dyn Write>) {
+    if args.is_empty() {
+        writeln_to_buffer("export: usage: export NAME=value", stdout_buffer);
+        state.last_status = 1;
+        return;
+    }
+
+    let mut ok = true;
+    for arg in args {
+        match arg.split_once('=') {
+            Some((name, value)) => {
+                env::set_var(name, value);
+                state.env.insert(name.to_string(), value.to_string());
+            }
+            None => {
+                writeln_to_buffer(
+                    &format!("export: not a valid identifier: {}", arg),
+                    stdout_buffer,
+                );
+                ok = false;
+            }
+        }
+    }
+    state.last_status = if ok { 0 } else { 1 };
+}
+
+/// `alias name='cmd args'` registers a substitution for `name` applied to the first token of
+/// a line before dispatch; `alias` with no arguments lists the aliases currently defined.
+fn alias_cmd(args: &[&str], state: &mut ShellState, stdout_buffer: &mut Box<dyn Write>) {
+    if args.is_empty() {
+        for (name, expansion) in &state.aliases {
+            writeln_to_buffer(&format!("alias {}='{}'", name, expansion), stdout_buffer);
+        }
+        state.last_status = 0;
+        return;
+    }
+
+    let mut ok = true;
+    for arg in args {
+        match arg.split_once('=') {
+            Some((name, expansion)) => {
+                state.aliases.insert(name.to_string(), expansion.to_string());
+            }
+            None => {
+                writeln_to_buffer(&format!("alias: not found: {}", arg), stdout_buffer);
+                ok = false;
+            }
+        }
     }
+    state.last_status = if ok { 0 } else { 1 };
 }
 
 fn find_external_cmd(env_path: &str, cmd: &str) -> Option<PathBuf> {
@@ -130,92 +431,236 @@ fn find_external_cmd(env_path: &str, cmd: &str) -> Option<PathBuf> {
     None
 }
 
+/// Spawns `cmds` as a child process (never `.output()`, so a non-final stage's stdout can be
+/// streamed straight into the next stage rather than buffered in our process). When `is_last`
+/// is false the child is returned so the caller can hand its stdout to the next stage and wait
+/// on it once the whole pipeline has drained; when true, stdout/stderr are captured here and
+/// written into `stdout_buffer`/`stderr_buffer` same as a standalone command always was.
+/// `stdin_bytes`, when present, is a builtin's captured output from the previous stage and is
+/// written into the child's stdin before anything is read back out.
 fn try_external_cmd(
     env_path: &str,
-    cmds: Vec<&str>,
+    cmds: &[&str],
+    stdin: Stdio,
+    stdin_bytes: Option<Vec<u8>>,
+    is_last: bool,
+    state: &mut ShellState,
     stdout_buffer: &mut Box<dyn Write>,
     stderr_buffer: &mut Box<dyn Write>,
-) {
+) -> Option<Child> {
     let cmd = cmds.first().unwrap(); // panicking here is ok if there's no first elem, since it should've been caught in the main
 
-    if let Some(_) = find_external_cmd(env_path, cmd) {
-        let output = Command::new(cmd)
-            .args(&cmds[1..])
-            .output()
-            .expect(format!("failed to execute: {}", cmds.join(" ")).as_str());
-        // redirect output to stdout_buffer to handle potential redirections
-        // using from_utf8_lossy to handle potential invalid characters
-        write_to_buffer(&String::from_utf8_lossy(&output.stdout), stdout_buffer);
-        write_to_buffer(&String::from_utf8_lossy(&output.stderr), stderr_buffer);
-    } else {
+    if find_external_cmd(env_path, cmd).is_none() {
         cmd_not_found(cmd, stdout_buffer);
+        state.last_status = 127;
+        return None;
+    }
+
+    let mut command = Command::new(cmd);
+    command
+        .args(&cmds[1..])
+        .stdin(if stdin_bytes.is_some() {
+            Stdio::piped()
+        } else {
+            stdin
+        })
+        .stdout(Stdio::piped())
+        // A mid-pipeline stage's stderr still goes straight to the terminal, same as in
+        // every other shell; only the final stage's stderr is subject to redirection.
+        .stderr(if is_last {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        });
+
+    let mut child = command
+        .spawn()
+        .expect(format!("failed to execute: {}", cmds.join(" ")).as_str());
+
+    if let Some(bytes) = stdin_bytes {
+        // Write then drop immediately so the child sees EOF. Builtin output is small enough
+        // that this can't deadlock the way two long-running external processes piped
+        // together could (those are wired through a real OS pipe instead, see run_pipeline).
+        let mut child_stdin = child.stdin.take().unwrap();
+        child_stdin.write_all(&bytes).ok();
     }
+
+    if !is_last {
+        return Some(child);
+    }
+
+    let mut stdout_bytes = Vec::new();
+    let mut stderr_bytes = Vec::new();
+    if let Some(mut out) = child.stdout.take() {
+        out.read_to_end(&mut stdout_bytes).unwrap();
+    }
+    if let Some(mut err) = child.stderr.take() {
+        err.read_to_end(&mut stderr_bytes).unwrap();
+    }
+    let status = child.wait().expect("failed to wait on child");
+    state.last_status = exit_code_from_status(&status);
+
+    // using from_utf8_lossy to handle potential invalid characters
+    write_to_buffer(&String::from_utf8_lossy(&stdout_bytes), stdout_buffer);
+    write_to_buffer(&String::from_utf8_lossy(&stderr_bytes), stderr_buffer);
+
+    None
 }
 
-fn parse_redirection<'a>(
-    cmds: &mut Vec<&str>,
-    stdout_buffer: &mut Box<dyn Write>,
-    stderr_buffer: &mut Box<dyn Write>,
-) -> Result<(), &'a str> {
-    for i in 0..cmds.len() {
-        if REDIR_WRITE_PATTERNS.contains(&cmds[i]) {
-            apply_redirection(false, i, cmds, stdout_buffer, stderr_buffer)?;
-            break;
+/// Mirrors the exit-status discipline shell scripts expect: a normal exit yields its code, a
+/// process killed by a signal yields 128+signal (the POSIX convention), same as `$?` reports
+/// in bash.
+fn exit_code_from_status(status: &ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1)
+}
+
+/// Where a single redirection points: a path to open, or another fd whose current
+/// destination it should be duplicated into (`2>&1`, `1>&2`).
+enum RedirectionTarget<'a> {
+    Path(&'a str),
+    Fd(u8),
+}
+
+/// One `<`/`>`/`>>`/`N>&M` redirection parsed off a command's tokens: which stream it affects
+/// (`0` stdin, `1` stdout, `2` stderr), where it points, and append-vs-truncate for file
+/// targets.
+struct Redirection<'a> {
+    fd: u8,
+    target: RedirectionTarget<'a>,
+    append: bool,
+}
+
+/// Scans `cmds` once, left to right, pulling out every `<`, `>`/`1>`/`2>` (and `>>` variants),
+/// and `2>&1`/`1>&2` token it finds and removing it from `cmds`, collecting one `Redirection`
+/// per match. Unlike the old break-after-first-match parser, this lets a single command carry
+/// more than one redirection (`cmd < in.txt > out.txt 2>&1`), applied in the order parsed.
+fn parse_redirection<'a>(cmds: &mut Vec<&'a str>) -> Result<Vec<Redirection<'a>>, &'static str> {
+    let mut redirections = Vec::new();
+    let mut i = 0;
+
+    while i < cmds.len() {
+        if REDIR_DUP_PATTERNS.contains(&cmds[i]) {
+            let fd = if cmds[i] == "2>&1" { 2 } else { 1 };
+            let target_fd = if fd == 2 { 1 } else { 2 };
+            redirections.push(Redirection {
+                fd,
+                target: RedirectionTarget::Fd(target_fd),
+                append: false,
+            });
+            cmds.remove(i);
+            continue;
         }
 
-        if REDIR_APPEND_PATTERNS.contains(&cmds[i]) {
-            apply_redirection(true, i, cmds, stdout_buffer, stderr_buffer)?;
-            break;
+        let is_input = cmds[i] == REDIR_INPUT_PATTERN;
+        let is_append = REDIR_APPEND_PATTERNS.contains(&cmds[i]);
+        if is_input || REDIR_WRITE_PATTERNS.contains(&cmds[i]) || is_append {
+            if i + 1 >= cmds.len() {
+                return Err("Redirection target missing");
+            }
+
+            let fd = if is_input {
+                0
+            } else if cmds[i].contains('2') {
+                2
+            } else {
+                1
+            };
+
+            redirections.push(Redirection {
+                fd,
+                target: RedirectionTarget::Path(cmds[i + 1]),
+                append: is_append,
+            });
+            cmds.drain(i..=i + 1);
+            continue;
         }
+
+        i += 1;
     }
-    Ok(())
+
+    Ok(redirections)
 }
 
-fn apply_redirection<'a>(
-    is_append: bool,
-    curr_idx: usize,
-    cmds: &mut Vec<&str>,
+/// Applies parsed redirections, in order, against the current command's stdin/stdout/stderr
+/// destinations. Applying them in parse order means `cmd > out.txt 2>&1` duplicates stderr
+/// into the stream already pointed at `out.txt`, while `cmd 2>&1 > out.txt` duplicates stderr
+/// into the stream stdout had *before* it was redirected, matching shell semantics.
+fn apply_redirections(
+    redirections: Vec<Redirection>,
+    stdin: &mut Stdio,
     stdout_buffer: &mut Box<dyn Write>,
     stderr_buffer: &mut Box<dyn Write>,
-) -> Result<(), &'a str> {
-    // expected format of cmds: [..., "redirection_source", ">>", "redirection_target", ...]
-    // ensure redirection target exists
-    if curr_idx + 1 >= cmds.len() {
-        return Err("Redirection target missing");
-    }
-
-    let redir_target = Path::new(cmds[curr_idx + 1]);
-    // assert target can be accessed (parent dir exists)
-    if !redir_target.parent().unwrap().exists() {
-        return Err("Redirection target doesn't exist");
-    }
-
-    let redir_buffer: Result<File, Error>;
-
-    if is_append {
-        redir_buffer = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(redir_target);
-    } else {
-        redir_buffer = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .open(redir_target);
+) -> Result<(), &'static str> {
+    let mut stdout_file: Option<File> = None;
+    let mut stderr_file: Option<File> = None;
+
+    for redirection in redirections {
+        match (redirection.fd, redirection.target) {
+            (0, RedirectionTarget::Path(path)) => {
+                let file = File::open(path).map_err(|_| "Redirection target doesn't exist")?;
+                *stdin = Stdio::from(file);
+            }
+            (1, RedirectionTarget::Path(path)) => {
+                let file = open_redirection_target(path, redirection.append)?;
+                *stdout_buffer = Box::new(
+                    file.try_clone()
+                        .map_err(|_| "Failed to open redirection target for writing")?,
+                );
+                stdout_file = Some(file);
+            }
+            (2, RedirectionTarget::Path(path)) => {
+                let file = open_redirection_target(path, redirection.append)?;
+                *stderr_buffer = Box::new(
+                    file.try_clone()
+                        .map_err(|_| "Failed to open redirection target for writing")?,
+                );
+                stderr_file = Some(file);
+            }
+            (1, RedirectionTarget::Fd(2)) => {
+                *stdout_buffer = match &stderr_file {
+                    Some(file) => Box::new(
+                        file.try_clone()
+                            .map_err(|_| "Failed to duplicate redirection target")?,
+                    ),
+                    None => Box::new(io::stderr()),
+                };
+            }
+            (2, RedirectionTarget::Fd(1)) => {
+                *stderr_buffer = match &stdout_file {
+                    Some(file) => Box::new(
+                        file.try_clone()
+                            .map_err(|_| "Failed to duplicate redirection target")?,
+                    ),
+                    None => Box::new(io::stdout()),
+                };
+            }
+            _ => return Err("Unsupported redirection"),
+        }
     }
 
-    if let Err(_) = redir_buffer {
-        return Err("Failed to open redirection target for writing");
-    }
+    Ok(())
+}
 
-    if cmds[curr_idx].contains("2") {
-        *stderr_buffer = Box::new(redir_buffer.unwrap());
-    } else {
-        *stdout_buffer = Box::new(redir_buffer.unwrap());
+/// Opens a `>`/`>>` redirection target, truncating on `>` and appending on `>>`, erroring the
+/// same way the old single-match parser did when the parent directory doesn't exist.
+fn open_redirection_target(target: &str, append: bool) -> Result<File, &'static str> {
+    let path = Path::new(target);
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            return Err("Redirection target doesn't exist");
+        }
     }
 
-    cmds.drain(curr_idx..=curr_idx + 1);
-    Ok(())
+    OpenOptions::new()
+        .create(true)
+        .append(append)
+        .write(true)
+        .truncate(!append)
+        .open(path)
+        .map_err(|_| "Failed to open redirection target for writing")
 }
 
 // using Box<dyn Write> since the function should accept both File and Stdout
@@ -0,0 +1,11 @@
+use std::collections::BTreeMap;
+
+/// Shell state carried across loop iterations: exported variables, aliases, and the exit
+/// status of the last command run (exposed to expansion as `$?`). Mirrors the `Config` struct
+/// moros keeps for its shell.
+#[derive(Default)]
+pub struct ShellState {
+    pub env: BTreeMap<String, String>,
+    pub aliases: BTreeMap<String, String>,
+    pub last_status: i32,
+}